@@ -7,7 +7,13 @@ import io
 import os
 import sys
 
-from omega_match import Compiler, Matcher, get_library_info, get_version
+from omega_match import (
+    Compiler,
+    Matcher,
+    get_library_info,
+    get_version,
+    is_compiled,
+)
 
 # Force stdout to use Unix-style line endings explicitly on Windows
 if os.name == "nt":
@@ -16,6 +22,24 @@ if os.name == "nt":
     )
 
 
+def check_inputs(compiled: str, input_file: str, input_name: str) -> None:
+    """Refuse swapped or identical COMPILED and input arguments."""
+    if (
+        os.path.exists(compiled)
+        and os.path.exists(input_file)
+        and os.path.samefile(compiled, input_file)
+    ):
+        sys.exit(
+            f"Error: COMPILED and {input_name} are the same file '{input_file}'. "
+            "Use --force to proceed anyway."
+        )
+    if is_compiled(input_file):
+        sys.exit(
+            f"Error: {input_name} '{input_file}' is a compiled pattern file; "
+            "the arguments may be swapped. Use --force to proceed anyway."
+        )
+
+
 def compile_mode(
     output_file: str,
     patterns_file: str,
@@ -126,6 +150,11 @@ def main() -> None:
     compile_parser.add_argument(
         "--elide-whitespace", action="store_true", help="Remove whitespace in patterns"
     )
+    compile_parser.add_argument(
+        "--force",
+        action="store_true",
+        help="Proceed even if the files look swapped or identical",
+    )
 
     # Match mode parser
     match_parser = subparsers.add_parser("match", help="Match patterns")
@@ -171,6 +200,11 @@ def main() -> None:
     match_parser.add_argument(
         "--chunk-size", type=int, default=0, help="Chunk size for parallel processing"
     )
+    match_parser.add_argument(
+        "--force",
+        action="store_true",
+        help="Proceed even if the files look swapped or identical",
+    )
 
     # Try to enable argcomplete (if available)
     try:
@@ -195,6 +229,12 @@ def main() -> None:
     if args.verbose:
         print(f"Running in {args.mode} mode with arguments: {args}")
 
+    if not args.force:
+        if args.mode == "compile":
+            check_inputs(args.compiled, args.patterns, "PATTERNS")
+        else:
+            check_inputs(args.compiled, args.haystack, "HAYSTACK")
+
     if args.mode == "compile":
         compile_mode(
             output_file=args.compiled,
@@ -8,6 +8,7 @@ from .omega_match import (
     PatternStoreStats,
     get_version,
     get_library_info,
+    is_compiled,
 )
 
 __all__ = [
@@ -18,4 +19,5 @@ __all__ = [
     "PatternStoreStats",
     "get_version",
     "get_library_info",
+    "is_compiled",
 ]
@@ -93,6 +93,15 @@ omega_list_matcher_compiler_get_pattern_store_stats(
 int omega_list_matcher_compiler_destroy(
     omega_list_matcher_compiler_t *restrict compiler);
 
+/**
+ * Discard the compiler without writing the compiled output file. Any existing
+ * file at the output path is left untouched.
+ * @param compiler Compiler handle.
+ * @return 0 on success, -1 on failure.
+ */
+int omega_list_matcher_compiler_abort(
+    omega_list_matcher_compiler_t *restrict compiler);
+
 /**
  * Check if a file is a compiled matcher file.
  * @param compiled_file Path to the matcher file to check.
@@ -457,6 +466,11 @@ def get_version() -> str:
     return ffi.string(version).decode("utf-8")
 
 
+def is_compiled(path: str) -> bool:
+    """Return True if path is a compiled pattern (.olm) file."""
+    return bool(_get_library().omega_list_matcher_is_compiled(path.encode("utf-8")))
+
+
 def get_library_info() -> Dict[str, str]:
     """Get information about the loaded native library variant.
 
@@ -526,7 +540,11 @@ class Compiler:
         _exc_val: Optional[BaseException],
         _exc_tb: Optional[BaseException],
     ) -> None:
-        self.destroy()
+        # Never publish a partial store when the block raised
+        if _exc_type is not None:
+            self.abort()
+        else:
+            self.destroy()
 
     def __del__(self):
         self.destroy()
@@ -554,8 +572,15 @@ class Compiler:
 
     def destroy(self) -> None:
         if hasattr(self, "_compiler") and self._compiler and C is not None:
-            self._lib.omega_list_matcher_compiler_destroy(self._compiler)
-            self._compiler = ffi.NULL
+            compiler, self._compiler = self._compiler, ffi.NULL
+            if self._lib.omega_list_matcher_compiler_destroy(compiler) != 0:
+                raise RuntimeError("Failed to write compiled file")
+
+    def abort(self) -> None:
+        if hasattr(self, "_compiler") and self._compiler and C is not None:
+            compiler, self._compiler = self._compiler, ffi.NULL
+            if self._lib.omega_list_matcher_compiler_abort(compiler) != 0:
+                raise RuntimeError("Failed to discard compiled file")
 
     @staticmethod
     def compile_from_filename(
@@ -1,5 +1,9 @@
 # tests/test_omega_match.py
 
+import subprocess
+import sys
+from pathlib import Path
+
 import pytest
 
 from omega_match.omega_match import (
@@ -8,13 +12,24 @@ from omega_match.omega_match import (
     MatchStats,
     PatternStoreStats,
     get_version,
+    is_compiled,
 )
 
+OLM_SCRIPT = Path(__file__).resolve().parent.parent / "olm.py"
+
 
 def write_file(path, lines):
     path.write_text("\n".join(lines), encoding="utf-8")
 
 
+def run_olm(*args):
+    return subprocess.run(
+        [sys.executable, str(OLM_SCRIPT), *map(str, args)],
+        capture_output=True,
+        text=True,
+    )
+
+
 def test_get_version():
     version = get_version()
     assert isinstance(version, str)
@@ -45,6 +60,21 @@ def test_compiler_add_patterns(tmp_path):
         assert results[0].match.lower() in [b"alpha", b"beta"]
 
 
+def test_compiler_exception_keeps_existing_file(tmp_path):
+    output_path = tmp_path / "existing.olm"
+    with Compiler(str(output_path)) as compiler:
+        compiler.add_pattern(b"Alpha")
+    original = output_path.read_bytes()
+
+    with pytest.raises(KeyError):
+        with Compiler(str(output_path)) as compiler:
+            compiler.add_pattern(b"Gamma")
+            raise KeyError("interrupted")
+
+    assert output_path.read_bytes() == original
+    assert [p.name for p in tmp_path.iterdir()] == ["existing.olm"]
+
+
 def test_compile_and_match(tmp_path):
     patterns = ["foo", "bar", "bazinga"]
     pat_file = tmp_path / "patterns.txt"
@@ -323,3 +353,57 @@ def test_line_exact_match(tmp_path):
         assert len(offsets) == 1
         assert matches == [b"exactline"]
         assert offsets[0] == 7  # Position after "before\n"
+
+
+def test_is_compiled(tmp_path):
+    pat_file = tmp_path / "patterns.txt"
+    write_file(pat_file, ["alpha", "beta"])
+    compiled_file = tmp_path / "matcher.olm"
+    Compiler.compile_from_filename(str(compiled_file), str(pat_file))
+
+    assert is_compiled(str(compiled_file))
+    assert not is_compiled(str(pat_file))
+    assert not is_compiled(str(tmp_path / "missing.olm"))
+
+
+def test_olm_rejects_swapped_and_same_file_arguments(tmp_path):
+    pat_file = tmp_path / "patterns.txt"
+    write_file(pat_file, ["alpha", "beta"])
+    compiled_file = tmp_path / "matcher.olm"
+    Compiler.compile_from_filename(str(compiled_file), str(pat_file))
+    compiled_bytes = compiled_file.read_bytes()
+
+    # A compiled file passed as PATTERNS or HAYSTACK
+    new_file = tmp_path / "new.olm"
+    result = run_olm("compile", new_file, compiled_file)
+    assert result.returncode != 0
+    assert "is a compiled pattern file" in result.stderr
+    assert not new_file.exists()
+    result = run_olm("match", pat_file, compiled_file)
+    assert result.returncode != 0
+    assert "is a compiled pattern file" in result.stderr
+
+    # The same file as COMPILED and input, also through a relative path
+    result = run_olm("compile", pat_file, pat_file)
+    assert result.returncode != 0
+    assert "are the same file" in result.stderr
+    assert pat_file.read_text(encoding="utf-8") == "alpha\nbeta"
+    dotdot_file = tmp_path / ".." / tmp_path.name / compiled_file.name
+    result = run_olm("match", compiled_file, dotdot_file)
+    assert result.returncode != 0
+    assert "are the same file" in result.stderr
+    assert compiled_file.read_bytes() == compiled_bytes
+
+
+def test_olm_force_proceeds(tmp_path):
+    pat_file = tmp_path / "patterns.txt"
+    write_file(pat_file, ["alpha", "beta"])
+    compiled_file = tmp_path / "matcher.olm"
+    Compiler.compile_from_filename(str(compiled_file), str(pat_file))
+
+    result = run_olm("match", "--force", compiled_file, compiled_file)
+    assert result.returncode == 0, result.stderr
+    forced_file = tmp_path / "forced.olm"
+    result = run_olm("compile", "--force", forced_file, compiled_file)
+    assert result.returncode == 0, result.stderr
+    assert is_compiled(str(forced_file))
@@ -5,6 +5,7 @@
 
 #include "attr.h"
 #include <stdint.h>
+#include <stdio.h>
 
 // Next power of two (used for bloom and hash table sizes)
 OLM_ALWAYS_INLINE static uint32_t next_power_of_two(uint32_t v) {
@@ -61,4 +62,24 @@ static inline char *format_u64(uint64_t v, char *restrict buf) {
 
 int oa_matcher_access(const char *restrict filename);
 
+// Returns 1 if both paths name the same existing file, 0 otherwise
+int oa_matcher_same_file(const char *restrict a, const char *restrict b);
+
+// Crash-safe output: regular (or missing) destinations are written to a hidden
+// temporary file (".<name>.tmp...") in the same directory and atomically
+// renamed over the destination on commit. Anything else (symlinks, FIFOs,
+// devices), and existing files in a directory that cannot be written to, are
+// written in place. A process killed before commit or discard leaves its
+// temporary behind.
+typedef struct {
+  FILE *fp;        // stream to write to
+  char *tmp_path;  // temporary file, or NULL when writing in place
+  char *dest_path; // file the temporary is renamed over
+} output_file_t;
+
+int oa_matcher_open_output(const char *restrict final_path,
+                           output_file_t *restrict out);
+int oa_matcher_commit_output(output_file_t *restrict out);
+int oa_matcher_discard_output(output_file_t *restrict out);
+
 #endif // OMEGA_LIST_MATCHER__DETAILS__UTIL_H
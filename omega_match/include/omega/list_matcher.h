@@ -89,6 +89,16 @@ omega_list_matcher_compiler_get_pattern_store_stats(
 int omega_list_matcher_compiler_destroy(
     omega_list_matcher_compiler_t *restrict compiler);
 
+/**
+ * Discard the compiler without writing the compiled output file. Any existing
+ * file at the output path is left untouched. The compiler is freed either way.
+ * @param compiler Compiler handle.
+ * @return 0 on success, -1 if the partial output could not be closed or
+ * removed.
+ */
+int omega_list_matcher_compiler_abort(
+    omega_list_matcher_compiler_t *restrict compiler);
+
 /**
  * Check if a file is a compiled matcher file.
  * @param compiled_file Path to the matcher file to check.
@@ -1,5 +1,6 @@
 // main.c
 
+#include <errno.h>
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
@@ -85,16 +86,35 @@ static void print_match_stats(const omega_match_stats_t *stats,
           (float)stats->total_comparisons / (float)results->count);
 }
 
-// Print match results to given file descriptor or console
-static void print_results_buffered_fd(const omega_match_results_t *results,
-                                      const int fd, const int use_console_api) {
+// Write all of buf to fd, resuming after short writes
+static int write_all(const int fd, const char *buf, size_t len) {
+  while (len > 0) {
+    const ssize_t written = write(fd, buf, len);
+    if (unlikely(written < 0)) {
+      if (errno == EINTR) {
+        continue;
+      }
+      perror("write");
+      return -1;
+    }
+    buf += written;
+    len -= (size_t)written;
+  }
+  return 0;
+}
+
+// Print match results to given file descriptor or console. Returns 0 on
+// success, -1 if the results could not be written.
+static int print_results_buffered_fd(const omega_match_results_t *results,
+                                     const int fd, const int use_console_api) {
   char *output_buffer = calloc(1, OUTPUT_BUFFER_SIZE);
   if (unlikely(!output_buffer)) {
     ABORT("calloc output_buffer");
   }
 
+  int result = 0;
   size_t pos = 0;
-  for (size_t i = 0; i < results->count; ++i) {
+  for (size_t i = 0; result == 0 && i < results->count; ++i) {
     int n = snprintf(output_buffer + pos, OUTPUT_BUFFER_SIZE - pos,
                      "%zu:%.*s\n",
                      results->matches[i].offset,
@@ -107,29 +127,22 @@ static void print_results_buffered_fd(const omega_match_results_t *results,
       if (use_console_api) {
         flush_buffer(output_buffer, pos);
       } else {
-        const ssize_t written = write(fd, output_buffer, pos);
-        if (unlikely(written < 0)) {
-          perror("write");
-          ABORT("write");
-        }
+        result = write_all(fd, output_buffer, pos);
       }
       pos = 0;
     }
   }
 
-  if (pos > 0) {
+  if (result == 0 && pos > 0) {
     if (use_console_api) {
       flush_buffer(output_buffer, pos);
     } else {
-      const ssize_t written = write(fd, output_buffer, pos);
-      if (unlikely(written < 0)) {
-        perror("write");
-        ABORT("write");
-      }
+      result = write_all(fd, output_buffer, pos);
     }
   }
 
   free(output_buffer);
+  return result;
 }
 
 // General help
@@ -155,6 +168,9 @@ static void compile_usage(const char *prog) {
   fprintf(stderr, "  --ignore-case         Ignore case in patterns\n");
   fprintf(stderr, "  --ignore-punctuation  Ignore punctuation in patterns\n");
   fprintf(stderr, "  --elide-whitespace    Remove whitespace in patterns\n");
+  fprintf(stderr,
+          "  --force               Proceed even if the files look swapped or "
+          "identical\n");
   fprintf(stderr, "  -v, --verbose         Enable verbose output\n");
   fprintf(stderr, "  -h, --help            Show this help message\n");
   fprintf(stderr, "\n");
@@ -184,6 +200,9 @@ static void match_usage(const char *prog) {
   fprintf(stderr, "  --threads N           Number of threads to use\n");
   fprintf(stderr,
           "  --chunk-size N        Chunk size for parallel processing\n");
+  fprintf(stderr,
+          "  --force               Proceed even if the files look swapped or "
+          "identical\n");
   fprintf(stderr, "  -v, --verbose         Enable verbose output\n");
   fprintf(stderr, "  -h, --help            Show this help message\n");
   fprintf(stderr, "\n");
@@ -281,7 +300,7 @@ int main(const int argc, char *argv[]) {
   int ignore_case = 0, ignore_punctuation = 0, elide_whitespace = 0,
       longest_only = 0, no_overlap = 0, word_boundary = 0, word_prefix = 0,
       word_suffix = 0, line_start = 0, line_end = 0, threads = 0,
-      chunk_size = 0, output_to_file = 0;
+      chunk_size = 0, output_to_file = 0, force = 0;
   const char *output_path = NULL;
 
   const struct option long_opts[] = {
@@ -297,6 +316,7 @@ int main(const int argc, char *argv[]) {
       {"line-start", no_argument, &line_start, 1},
       {"line-end", no_argument, &line_end, 1},
       {"verbose", no_argument, &verbose, 1},
+      {"force", no_argument, &force, 1},
       {"output", required_argument, NULL, 'o'},
       {"threads", required_argument, NULL, 't'},
       {"chunk-size", required_argument, NULL, 'C'},
@@ -369,26 +389,57 @@ int main(const int argc, char *argv[]) {
   const char *arg1 = new_argv[optind];
   const char *arg2 = new_argv[optind + 1];
 
-  // Open output if requested
-  FILE *out_fp = stdout;
+  // Catch swapped or duplicated arguments, which otherwise overwrite the
+  // patterns file or produce baffling match output
+  if (!force) {
+    const char *input_name =
+        mode_flag == MODE_COMPILE ? "PATTERNS" : "HAYSTACK";
+    if (oa_matcher_same_file(arg1, arg2)) {
+      fprintf(stderr,
+              "Error: COMPILED and %s are the same file '%s'. Use --force to "
+              "proceed anyway.\n",
+              input_name, arg2);
+      free(new_argv);
+      return EXIT_FAILURE;
+    }
+    if (omega_list_matcher_is_compiled(arg2)) {
+      fprintf(stderr,
+              "Error: %s '%s' is a compiled pattern file; the arguments may "
+              "be swapped. Use --force to proceed anyway.\n",
+              input_name, arg2);
+      free(new_argv);
+      return EXIT_FAILURE;
+    }
+  }
+
+  // Open output if requested; results go to a temporary file that replaces
+  // the output file only once they are complete
+  output_file_t out_file = {0};
   int out_fd = STDOUT_FILENO;
   int use_console_api = 0;
 #ifdef _WIN32
   use_console_api = !output_to_file;
 #endif
   if (output_to_file) {
-    out_fp = fopen(output_path, "wb");
-    if (!out_fp) { perror("fopen output file"); return EXIT_FAILURE; }
-    out_fd = fileno(out_fp);
+    if (oa_matcher_open_output(output_path, &out_file) != 0) {
+      perror("open output file");
+      return EXIT_FAILURE;
+    }
+    out_fd = fileno(out_file.fp);
   }
 
   // Dispatch
   switch (mode_flag) {
   case MODE_COMPILE: {
     omega_match_pattern_store_stats_t pattern_store_stats = {0};
-    omega_list_matcher_compile_patterns_filename(
-        arg1, arg2, ignore_case, ignore_punctuation, elide_whitespace,
-        &pattern_store_stats);
+    if (omega_list_matcher_compile_patterns_filename(
+            arg1, arg2, ignore_case, ignore_punctuation, elide_whitespace,
+            &pattern_store_stats) != 0) {
+      fprintf(stderr, "Error: Failed to write compiled file '%s'.\n", arg1);
+      oa_matcher_discard_output(&out_file);
+      free(new_argv);
+      return EXIT_FAILURE;
+    }
     if (verbose) {
       print_pattern_store_stats(&pattern_store_stats, stderr);
       fputs("Compile completed successfully.\n", stderr);
@@ -406,6 +457,7 @@ int main(const int argc, char *argv[]) {
     // Check if matcher creation was successful
     if (!matcher) {
       fprintf(stderr, "Error: Failed to create matcher from '%s'.\n", arg1);
+      oa_matcher_discard_output(&out_file);
       free(new_argv);
       return EXIT_FAILURE;
     }
@@ -421,6 +473,7 @@ int main(const int argc, char *argv[]) {
     if (!haystack) {
       fprintf(stderr, "Error: Failed to map file '%s'.\n", arg2);
       omega_list_matcher_destroy(matcher);
+      oa_matcher_discard_output(&out_file);
       free(new_argv);
       return EXIT_FAILURE;
     }
@@ -430,6 +483,7 @@ int main(const int argc, char *argv[]) {
       if (omega_matcher_set_num_threads(matcher, threads) != 0) {
         fprintf(stderr, "Error: thread count must be 1..%d\n",
                 omp_get_max_threads());
+        oa_matcher_discard_output(&out_file);
         free(new_argv);
         return EXIT_FAILURE;
       }
@@ -437,6 +491,7 @@ int main(const int argc, char *argv[]) {
     if (chunk_size > 0) {
       if (omega_matcher_set_chunk_size(matcher, chunk_size) != 0) {
         fputs("Error: chunk size must be > 0\n", stderr);
+        oa_matcher_discard_output(&out_file);
         free(new_argv);
         return EXIT_FAILURE;
       }
@@ -455,19 +510,30 @@ int main(const int argc, char *argv[]) {
       print_match_stats(&stats, results, stderr);
     }
     // Print the results
-    print_results_buffered_fd(results, out_fd, use_console_api);
+    const int printed =
+        print_results_buffered_fd(results, out_fd, use_console_api);
     // Free the results
     omega_match_results_destroy(results);
     // Destroy the matcher
     omega_list_matcher_destroy(matcher);
     // Unmap the haystack file
     omega_matcher_unmap_file(haystack, haystack_size);
+    if (printed != 0) {
+      fputs("Error: Failed to write match results.\n", stderr);
+      oa_matcher_discard_output(&out_file);
+      free(new_argv);
+      return EXIT_FAILURE;
+    }
   } break;
   default:
     // This should never happen
     ABORT("Invalid mode");
   }
-  if (output_to_file) fclose(out_fp);
+  if (output_to_file && oa_matcher_commit_output(&out_file) != 0) {
+    perror("commit output file");
+    free(new_argv);
+    return EXIT_FAILURE;
+  }
   free(new_argv);
   return EXIT_SUCCESS;
 }
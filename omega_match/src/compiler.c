@@ -31,6 +31,7 @@ struct omega_list_matcher_compiler_struct {
   uint32_t compiler_flags;
   short_matcher_builder_t smb;
   const char *compiled_file_name;
+  output_file_t compiled_output;
   transform_table_t *transform_table;
   FILE *compiled_fp;
   pattern_store_t *store;
@@ -132,24 +133,28 @@ static int short_matcher_add(omega_list_matcher_compiler_t *restrict compiler,
 omega_list_matcher_compiler_t *omega_list_matcher_compiler_create(
     const char *restrict compiled_file, const int case_insensitive,
     const int ignore_punctuation, const int elide_whitespace) {
-  FILE *out = fopen(compiled_file, "wb");
-  if (unlikely(!out)) {
-    perror("fopen compiled_file");
-    ABORT("fopen compiled_file");
+  // Write to a temporary file that is renamed over compiled_file only once
+  // the store is complete, so an interrupted compile never leaves a partial
+  // .olm behind
+  output_file_t output;
+  if (unlikely(oa_matcher_open_output(compiled_file, &output) != 0)) {
+    perror("open compiled_file");
+    ABORT("open compiled_file");
   }
+  FILE *out = output.fp;
   const compiled_header_t header = {0};
   if (unlikely(fwrite(&header, sizeof(header), 1, out) != 1)) {
     ABORT("fwrite header");
   }
   omega_list_matcher_compiler_t *compiler = calloc(1, sizeof(*compiler));
   if (unlikely(!compiler)) {
-    fclose(out);
+    oa_matcher_discard_output(&output);
     ABORT("calloc compiler");
   }
 
   compiler->store = init_pattern_store(out, &compiler->stats);
   if (unlikely(!compiler->store)) {
-    fclose(out);
+    oa_matcher_discard_output(&output);
     free(compiler);
     ABORT("init_pattern_store");
   }
@@ -158,14 +163,14 @@ omega_list_matcher_compiler_t *omega_list_matcher_compiler_create(
   if (case_insensitive || ignore_punctuation || elide_whitespace) {
     compiler->transform_table = malloc(sizeof(transform_table_t));
     if (unlikely(!compiler->transform_table)) {
-      fclose(out);
+      oa_matcher_discard_output(&output);
       free(compiler);
       ABORT("malloc pattern transform");
     }
     if (unlikely(transform_init(compiler->transform_table, case_insensitive,
                                 ignore_punctuation, elide_whitespace) != 0)) {
       free(compiler->transform_table);
-      fclose(out);
+      oa_matcher_discard_output(&output);
       free(compiler);
       ABORT("pattern_transform_init");
     }
@@ -184,11 +189,12 @@ omega_list_matcher_compiler_t *omega_list_matcher_compiler_create(
   if (unlikely(!compiler->compiled_file_name)) {
     transform_free(compiler->transform_table);
     free(compiler->transform_table);
-    fclose(out);
+    oa_matcher_discard_output(&output);
     free(compiler);
     ABORT("strdup compiled_file_name");
   }
   compiler->compiled_fp = out;
+  compiler->compiled_output = output;
   hash_table_init(&compiler->table, 0);
   short_matcher_init(compiler);
   return compiler;
@@ -238,6 +244,18 @@ omega_list_matcher_compiler_get_pattern_store_stats(
   return &compiler->stats;
 }
 
+// Free the in-memory compiler state once the output has been handled
+static void compiler_free(omega_list_matcher_compiler_t *restrict compiler) {
+  free((void *)compiler->compiled_file_name);
+  hash_table_free(&compiler->table);
+  if (compiler->transform_table) {
+    transform_free(compiler->transform_table);
+    free(compiler->transform_table);
+  }
+  short_matcher_free(compiler);
+  free(compiler);
+}
+
 int omega_list_matcher_compiler_destroy(
     omega_list_matcher_compiler_t *restrict compiler) {
   if (unlikely(!compiler)) {
@@ -366,17 +384,30 @@ int omega_list_matcher_compiler_destroy(
 
   fseek(compiler->compiled_fp, 0, SEEK_SET);
   fwrite(&header, sizeof(compiled_header_t), 1, compiler->compiled_fp);
-  fflush(compiler->compiled_fp);
-  fclose(compiler->compiled_fp);
-  free((void *)compiler->compiled_file_name);
-  hash_table_free(&compiler->table);
-  if (compiler->transform_table) {
-    transform_free(compiler->transform_table);
-    free(compiler->transform_table);
+
+  // Publish the completed store under its final name
+  const int result = oa_matcher_commit_output(&compiler->compiled_output);
+  if (unlikely(result != 0)) {
+    perror("commit compiled_file");
   }
-  short_matcher_free(compiler);
-  free(compiler);
-  return 0;
+  compiler_free(compiler);
+  return result;
+}
+
+int omega_list_matcher_compiler_abort(
+    omega_list_matcher_compiler_t *restrict compiler) {
+  if (unlikely(!compiler)) {
+    ABORT("omega_list_matcher_compiler_abort: invalid arguments");
+  }
+
+  // Drop the partial store, leaving any existing compiled file untouched
+  destroy_pattern_store(compiler->store);
+  const int result = oa_matcher_discard_output(&compiler->compiled_output);
+  if (unlikely(result != 0)) {
+    perror("discard compiled_file");
+  }
+  compiler_free(compiler);
+  return result;
 }
 
 int omega_list_matcher_compile_patterns(
@@ -3,11 +3,75 @@
 #include <ctype.h> // for toupper
 #include <stddef.h>
 #include <stdint.h>
+#include <string.h>
 
 #include "omega/details/common.h"
 #include "omega/details/util.h"
 #include "omega/list_matcher.h"
 
+// Release the paths held by an output and reset it
+static void output_file_reset(output_file_t *restrict out) {
+  free(out->tmp_path);
+  free(out->dest_path);
+  out->fp = NULL;
+  out->tmp_path = NULL;
+  out->dest_path = NULL;
+}
+
+// Close an output without publishing it. A temporary file is removed and the
+// destination is left untouched; in-place output is only closed. Returns -1 if
+// the stream could not be closed or the temporary could not be removed.
+int oa_matcher_discard_output(output_file_t *restrict out) {
+  if (!out) {
+    return 0;
+  }
+  int result = 0;
+  if (out->fp && fclose(out->fp) != 0) {
+    result = -1;
+  }
+  if (out->tmp_path && remove(out->tmp_path) != 0) {
+    result = -1;
+  }
+  output_file_reset(out);
+  return result;
+}
+
+// Write final_path directly, for destinations that cannot be replaced by a
+// rename
+static int open_output_in_place(const char *restrict final_path,
+                                output_file_t *restrict out) {
+  out->fp = fopen(final_path, "wb");
+  return out->fp ? 0 : -1;
+}
+
+// Build "<dir>/.<name>" for final_path with room for suffix_size more bytes.
+// The temporary lives next to the destination so the rename stays on one
+// filesystem, and the leading dot keeps leftovers from a crash out of globs.
+static char *output_temp_prefix(const char *restrict final_path,
+                                const size_t suffix_size) {
+  const char *name = final_path;
+  for (const char *p = final_path; *p; ++p) {
+#ifdef _WIN32
+    if (*p == '\\' || *p == ':') {
+      name = p + 1;
+    }
+#endif
+    if (*p == '/') {
+      name = p + 1;
+    }
+  }
+  const size_t dir_len = (size_t)(name - final_path);
+  const size_t name_len = strlen(name);
+  char *prefix = malloc(dir_len + 1 + name_len + 1 + suffix_size);
+  if (unlikely(!prefix)) {
+    return NULL;
+  }
+  memcpy(prefix, final_path, dir_len);
+  prefix[dir_len] = '.';
+  memcpy(prefix + dir_len + 1, name, name_len + 1);
+  return prefix;
+}
+
 #ifdef _WIN32
 #ifndef _WIN32_WINNT
 #define _WIN32_WINNT 0x0602 // Compile for Windows 8 or later
@@ -35,6 +99,7 @@
 #endif
 #endif
 
+#include <errno.h>
 #include <io.h> // for _get_osfhandle
 #include <memoryapi.h>
 #include <windows.h>
@@ -48,6 +113,128 @@ int oa_matcher_access(const char *restrict filename) {
   return _access(filename, 4); // 4 = R_OK
 }
 
+// Compare volume serial number and file index, which identify a file
+// regardless of the path used to reach it
+int oa_matcher_same_file(const char *restrict a, const char *restrict b) {
+  if (!a || !b) {
+    ABORT("oa_matcher_same_file: invalid filename");
+  }
+
+  BY_HANDLE_FILE_INFORMATION info[2];
+  const char *paths[2] = {a, b};
+  for (int i = 0; i < 2; ++i) {
+    HANDLE h = CreateFileA(paths[i], 0,
+                           FILE_SHARE_READ | FILE_SHARE_WRITE |
+                               FILE_SHARE_DELETE,
+                           NULL, OPEN_EXISTING, FILE_ATTRIBUTE_NORMAL, NULL);
+    if (h == INVALID_HANDLE_VALUE) {
+      return 0;
+    }
+    const BOOL ok = GetFileInformationByHandle(h, &info[i]);
+    CloseHandle(h);
+    if (!ok) {
+      return 0;
+    }
+  }
+  return info[0].dwVolumeSerialNumber == info[1].dwVolumeSerialNumber &&
+         info[0].nFileIndexHigh == info[1].nFileIndexHigh &&
+         info[0].nFileIndexLow == info[1].nFileIndexLow;
+}
+
+#define TEMP_SUFFIX ".tmpXXXXXX"
+
+// Returns 1 if final_path exists and must be written in place: devices (NUL,
+// CON) cannot be renamed over, and reparse points would be replaced rather
+// than followed
+static int write_in_place(const char *restrict final_path) {
+  const DWORD attrs = GetFileAttributesA(final_path);
+  if (attrs != INVALID_FILE_ATTRIBUTES &&
+      (attrs & FILE_ATTRIBUTE_REPARSE_POINT)) {
+    return 1;
+  }
+  HANDLE h = CreateFileA(final_path, 0,
+                         FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                         NULL, OPEN_EXISTING, FILE_ATTRIBUTE_NORMAL, NULL);
+  if (h == INVALID_HANDLE_VALUE) {
+    return 0;
+  }
+  const DWORD type = GetFileType(h);
+  CloseHandle(h);
+  return type != FILE_TYPE_DISK;
+}
+
+// Open final_path for crash-safe writing.
+int oa_matcher_open_output(const char *restrict final_path,
+                           output_file_t *restrict out) {
+  if (!final_path || !out) {
+    ABORT("oa_matcher_open_output: invalid arguments");
+  }
+  memset(out, 0, sizeof(*out));
+
+  if (write_in_place(final_path)) {
+    return open_output_in_place(final_path, out);
+  }
+
+  out->dest_path = strdup(final_path);
+  out->tmp_path = output_temp_prefix(final_path, sizeof(TEMP_SUFFIX));
+  if (!out->dest_path || !out->tmp_path) {
+    output_file_reset(out);
+    return -1;
+  }
+  const size_t len = strlen(out->tmp_path);
+  memcpy(out->tmp_path + len, TEMP_SUFFIX, sizeof(TEMP_SUFFIX));
+
+  // "x" makes the create exclusive, so a file that appeared after
+  // _mktemp_s picked the name is never opened
+  if (_mktemp_s(out->tmp_path, len + sizeof(TEMP_SUFFIX)) != 0 ||
+      !(out->fp = fopen(out->tmp_path, "wbx"))) {
+    const int err = errno;
+    output_file_reset(out);
+    // A directory that cannot be written to still allows rewriting an
+    // existing writable file
+    if ((err == EACCES || err == EROFS) && _access(final_path, 2) == 0) {
+      return open_output_in_place(final_path, out);
+    }
+    errno = err;
+    return -1;
+  }
+  return 0;
+}
+
+// Flush the output to disk and move the temporary over the destination.
+int oa_matcher_commit_output(output_file_t *restrict out) {
+  if (!out || !out->fp) {
+    ABORT("oa_matcher_commit_output: invalid arguments");
+  }
+
+  // A write that failed earlier leaves the error flag set even when the final
+  // flush succeeds
+  if (ferror(out->fp)) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  if (!out->tmp_path) {
+    const int result = fclose(out->fp) == 0 ? 0 : -1;
+    output_file_reset(out);
+    return result;
+  }
+  if (fflush(out->fp) != 0 || _commit(_fileno(out->fp)) != 0) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  const int closed = fclose(out->fp);
+  out->fp = NULL;
+  // MOVEFILE_WRITE_THROUGH only returns once the move has reached the disk
+  if (closed != 0 ||
+      !MoveFileExA(out->tmp_path, out->dest_path,
+                   MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH)) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  output_file_reset(out);
+  return 0;
+}
+
 uint8_t *omega_matcher_map_file(FILE *restrict file, size_t *restrict size,
                                 const int prefetch_sequential) {
   if (!file) {
@@ -128,6 +315,7 @@ int omega_matcher_unmap_file(const uint8_t *addr, const size_t size) {
 
 #else // POSIX
 
+#include <errno.h>
 #include <fcntl.h>
 #include <stdlib.h>
 #include <sys/mman.h>
@@ -143,6 +331,130 @@ int oa_matcher_access(const char *restrict filename) {
   return access(filename, R_OK);
 }
 
+// Compare device and inode, which identify a file regardless of the path used
+// to reach it (symlinks, hard links, relative paths)
+int oa_matcher_same_file(const char *restrict a, const char *restrict b) {
+  if (unlikely(!a || !b)) {
+    ABORT("oa_matcher_same_file: invalid filename");
+  }
+
+  struct stat st_a, st_b;
+  if (stat(a, &st_a) != 0 || stat(b, &st_b) != 0) {
+    return 0;
+  }
+  return st_a.st_dev == st_b.st_dev && st_a.st_ino == st_b.st_ino;
+}
+
+#define TEMP_NAME_MAX (48) // ".tmp<pid>.<attempt>" and the terminator
+#define TEMP_ATTEMPTS (100)
+
+// Open final_path for crash-safe writing.
+int oa_matcher_open_output(const char *restrict final_path,
+                           output_file_t *restrict out) {
+  if (unlikely(!final_path || !out)) {
+    ABORT("oa_matcher_open_output: invalid arguments");
+  }
+  memset(out, 0, sizeof(*out));
+
+  // Symlinks are written through rather than resolved: /dev/stdout and
+  // friends point at descriptors whose file must not be renamed over. FIFOs,
+  // devices and the like cannot be renamed over either
+  struct stat st;
+  const int exists = lstat(final_path, &st) == 0;
+  if (exists && !S_ISREG(st.st_mode)) {
+    return open_output_in_place(final_path, out);
+  }
+
+  out->dest_path = strdup(final_path);
+  out->tmp_path = output_temp_prefix(final_path, TEMP_NAME_MAX);
+  if (unlikely(!out->dest_path || !out->tmp_path)) {
+    output_file_reset(out);
+    return -1;
+  }
+  const size_t len = strlen(out->tmp_path);
+  int fd = -1;
+  for (unsigned attempt = 0; fd < 0 && attempt < TEMP_ATTEMPTS; ++attempt) {
+    snprintf(out->tmp_path + len, TEMP_NAME_MAX, ".tmp%ld.%u", (long)getpid(),
+             attempt);
+    // O_EXCL makes the create exclusive and mode 0666 lets the umask apply
+    fd = open(out->tmp_path, O_WRONLY | O_CREAT | O_EXCL | O_CLOEXEC, 0666);
+    if (fd < 0 && errno != EEXIST) {
+      break;
+    }
+  }
+  if (unlikely(fd < 0)) {
+    const int err = errno;
+    output_file_reset(out);
+    // A directory that cannot be written to still allows rewriting an
+    // existing writable file, as a plain fopen would
+    if (exists && (err == EACCES || err == EROFS) &&
+        access(final_path, W_OK) == 0) {
+      return open_output_in_place(final_path, out);
+    }
+    errno = err;
+    return -1;
+  }
+
+  // Keep the permissions of a file being replaced
+  if (unlikely((exists && fchmod(fd, st.st_mode & 07777) != 0) ||
+               !(out->fp = fdopen(fd, "wb")))) {
+    close(fd);
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  return 0;
+}
+
+// Sync the directory containing path so a rename within it is durable. This
+// is best effort: the rename has already published the file, and a directory
+// that cannot be opened (e.g. mode 0733) or synced does not undo it.
+static void sync_parent_dir(const char *restrict path) {
+  const char *slash = strrchr(path, '/');
+  char *dir = slash ? strndup(path, slash == path ? 1 : (size_t)(slash - path))
+                    : strdup(".");
+  if (unlikely(!dir)) {
+    return;
+  }
+  const int fd = open(dir, O_RDONLY | O_DIRECTORY | O_CLOEXEC);
+  free(dir);
+  if (fd >= 0) {
+    fsync(fd);
+    close(fd);
+  }
+}
+
+// Flush the output to disk and rename the temporary over the destination.
+int oa_matcher_commit_output(output_file_t *restrict out) {
+  if (unlikely(!out || !out->fp)) {
+    ABORT("oa_matcher_commit_output: invalid arguments");
+  }
+
+  // A write that failed earlier leaves the error flag set even when the final
+  // flush succeeds
+  if (unlikely(ferror(out->fp))) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  if (!out->tmp_path) {
+    const int result = fclose(out->fp) == 0 ? 0 : -1;
+    output_file_reset(out);
+    return result;
+  }
+  if (unlikely(fflush(out->fp) != 0 || fsync(fileno(out->fp)) != 0)) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  const int closed = fclose(out->fp);
+  out->fp = NULL;
+  if (unlikely(closed != 0 || rename(out->tmp_path, out->dest_path) != 0)) {
+    oa_matcher_discard_output(out);
+    return -1;
+  }
+  sync_parent_dir(out->dest_path);
+  output_file_reset(out);
+  return 0;
+}
+
 // Map a file descriptor into memory for reading.
 static inline uint8_t *map_file_descriptor(const int file_descriptor,
                                            size_t *restrict size,
@@ -0,0 +1,155 @@
+#!/usr/bin/env python3
+"""
+Atomic output test for omega_match.
+A match that fails after the output file is opened must leave an existing
+output file untouched and must not leave temporary files behind. Outputs that
+are not regular files (symlinks, FIFOs) must be written through, not replaced.
+"""
+
+import os
+import resource
+import signal
+import stat
+import subprocess
+import sys
+import tempfile
+import threading
+from pathlib import Path
+
+# Add the parent directory to the path so we can import omega_test
+sys.path.insert(0, str(Path(__file__).parent))
+from omega_test import OmegaMatchTest
+
+class AtomicOutputTest(OmegaMatchTest):
+    """Atomic output test for omega_match."""
+
+    def run_test(self):
+        """Run the test."""
+        # Define file paths
+        patterns_file = self.data_dir / "small_pats.txt"
+        haystack_file = self.data_dir / "small_hay.txt"
+        self.validate_files({
+            patterns_file: "Patterns file",
+            haystack_file: "Haystack file"
+        })
+
+        with tempfile.TemporaryDirectory() as temp_dir:
+            work_dir = Path(temp_dir)
+            compiled_file = work_dir / f"patterns{self.args.compiled_ext}"
+            output_file = work_dir / "results.txt"
+
+            self.logger.info(f"Compiling patterns from {patterns_file} to {compiled_file}")
+            self.run_command([
+                self.bin_path,
+                "compile",
+                str(compiled_file),
+                str(patterns_file)
+            ], check=True)
+
+            # An out-of-range thread count is rejected after the output is opened
+            output_file.write_text("previous results\n")
+            self.logger.info("Running a matcher invocation that is expected to fail")
+            result = self.run_command([
+                self.bin_path,
+                "match",
+                "--output", str(output_file),
+                "--threads", "1000000",
+                str(compiled_file),
+                str(haystack_file)
+            ], check=False)
+            if result.returncode == 0:
+                raise AssertionError("Matcher unexpectedly succeeded")
+
+            if output_file.read_text() != "previous results\n":
+                raise AssertionError("Existing output file was modified by a failed run")
+
+            leftovers = sorted(p.name for p in work_dir.iterdir()
+                               if p not in (compiled_file, output_file))
+            if leftovers:
+                raise AssertionError(f"Temporary files left behind: {leftovers}")
+
+            # A successful run replaces the output file
+            self.run_command([
+                self.bin_path,
+                "match",
+                "--output", str(output_file),
+                str(compiled_file),
+                str(haystack_file)
+            ], check=True)
+            if output_file.read_text() == "previous results\n":
+                raise AssertionError("Output file was not replaced by a successful run")
+            expected = output_file.read_text()
+
+            if os.name != "nt":
+                self.check_posix_targets(work_dir, compiled_file, haystack_file, expected)
+
+    def match_to(self, output, compiled_file, haystack_file):
+        """Run a successful match writing to output."""
+        self.run_command([
+            self.bin_path,
+            "match",
+            "--output", str(output),
+            str(compiled_file),
+            str(haystack_file)
+        ], check=True)
+
+    def check_posix_targets(self, work_dir, compiled_file, haystack_file, expected):
+        """Check permission, symlink and FIFO outputs."""
+        # A replaced file keeps its permissions
+        private_file = work_dir / "private.txt"
+        private_file.write_text("previous results\n")
+        private_file.chmod(0o600)
+        self.match_to(private_file, compiled_file, haystack_file)
+        if stat.S_IMODE(private_file.stat().st_mode) != 0o600:
+            raise AssertionError("Output file permissions were not preserved")
+
+        # A failed write is reported and discards the partial results
+        limited_file = work_dir / "limited.txt"
+        limited_file.write_text("previous results\n")
+
+        def limit_file_size():
+            signal.signal(signal.SIGXFSZ, signal.SIG_IGN)
+            resource.setrlimit(resource.RLIMIT_FSIZE, (8, 8))
+
+        cmd = [self.bin_path, "match", "--output", str(limited_file),
+               str(compiled_file), str(haystack_file)]
+        self.logger.info(f"Running command with an 8 byte file size limit: {cmd}")
+        result = subprocess.run(cmd, capture_output=True, text=True,
+                                preexec_fn=limit_file_size)
+        if result.returncode != 1:
+            raise AssertionError(
+                f"Failed write exited with {result.returncode}, expected 1")
+        if limited_file.read_text() != "previous results\n":
+            raise AssertionError("Existing output file was modified by a failed write")
+        leftovers = sorted(p.name for p in work_dir.iterdir() if ".tmp" in p.name)
+        if leftovers:
+            raise AssertionError(f"Temporary files left behind: {leftovers}")
+
+        # A symlink stays a symlink and its target receives the results
+        target_file = work_dir / "target.txt"
+        target_file.write_text("previous results\n")
+        link_file = work_dir / "link.txt"
+        link_file.symlink_to(target_file.name)
+        self.match_to(link_file, compiled_file, haystack_file)
+        if not link_file.is_symlink():
+            raise AssertionError("Symlinked output was replaced")
+        if target_file.read_text() != expected:
+            raise AssertionError("Symlink target did not receive the results")
+
+        # A FIFO stays a FIFO and its reader receives the results
+        fifo_file = work_dir / "results.fifo"
+        os.mkfifo(fifo_file)
+        received = []
+        reader = threading.Thread(
+            target=lambda: received.append(fifo_file.read_text()))
+        reader.start()
+        self.match_to(fifo_file, compiled_file, haystack_file)
+        reader.join(timeout=30)
+        if not stat.S_ISFIFO(fifo_file.stat().st_mode):
+            raise AssertionError("FIFO output was replaced")
+        if received != [expected]:
+            raise AssertionError("FIFO reader did not receive the results")
+
+if __name__ == "__main__":
+    test = AtomicOutputTest()
+    sys.exit(test.main())
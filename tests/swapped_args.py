@@ -0,0 +1,110 @@
+#!/usr/bin/env python3
+"""
+Swapped argument test for omega_match.
+Passing the compiled file where a patterns or haystack file is expected, or
+the same file for both, must fail with a descriptive error unless --force is
+given.
+"""
+
+import os
+import sys
+import tempfile
+from pathlib import Path
+
+# Add the parent directory to the path so we can import omega_test
+sys.path.insert(0, str(Path(__file__).parent))
+from omega_test import OmegaMatchTest
+
+class SwappedArgsTest(OmegaMatchTest):
+    """Swapped argument test for omega_match."""
+
+    def expect_failure(self, cmd, message):
+        """Run cmd and require a non-zero exit with message on stderr."""
+        result = self.run_command(cmd, check=False)
+        if result.returncode == 0:
+            raise AssertionError(f"Command unexpectedly succeeded: {cmd}")
+        if message not in result.stderr:
+            raise AssertionError(f"Expected '{message}' in stderr, got: {result.stderr}")
+
+    def run_test(self):
+        """Run the test."""
+        # Define file paths
+        patterns_file = self.data_dir / "small_pats.txt"
+        haystack_file = self.data_dir / "small_hay.txt"
+        self.validate_files({
+            patterns_file: "Patterns file",
+            haystack_file: "Haystack file"
+        })
+
+        with tempfile.TemporaryDirectory() as temp_dir:
+            compiled_file = Path(temp_dir) / f"patterns{self.args.compiled_ext}"
+
+            self.logger.info(f"Compiling patterns from {patterns_file} to {compiled_file}")
+            self.run_command([
+                self.bin_path,
+                "compile",
+                str(compiled_file),
+                str(patterns_file)
+            ], check=True)
+            compiled_bytes = compiled_file.read_bytes()
+
+            # Compiled file given as the patterns input
+            self.expect_failure([
+                self.bin_path, "compile",
+                str(haystack_file), str(compiled_file)
+            ], "is a compiled pattern file")
+
+            # Same file given as both the output and the patterns input
+            self.expect_failure([
+                self.bin_path, "compile",
+                str(compiled_file), str(compiled_file)
+            ], "are the same file")
+            if compiled_file.read_bytes() != compiled_bytes:
+                raise AssertionError("Compiled file was modified by a rejected compile")
+
+            # Compiled file given as the haystack
+            self.expect_failure([
+                self.bin_path, "match",
+                str(haystack_file), str(compiled_file)
+            ], "is a compiled pattern file")
+
+            # Same file given as both the compiled file and the haystack
+            self.expect_failure([
+                self.bin_path, "match",
+                str(compiled_file), str(compiled_file)
+            ], "are the same file")
+
+            # The same file reached through a different path is still caught
+            parent = compiled_file.parent
+            dotdot_path = parent / ".." / parent.name / compiled_file.name
+            self.expect_failure([
+                self.bin_path, "match",
+                str(compiled_file), str(dotdot_path)
+            ], "are the same file")
+            if os.name != "nt":
+                link_file = Path(temp_dir) / "link.olm"
+                link_file.symlink_to(compiled_file.name)
+                self.expect_failure([
+                    self.bin_path, "compile",
+                    str(link_file), str(compiled_file)
+                ], "are the same file")
+                if compiled_file.read_bytes() != compiled_bytes:
+                    raise AssertionError(
+                        "Compiled file was modified through a symlink")
+
+            # The override proceeds
+            self.run_command([
+                self.bin_path, "match", "--force",
+                str(compiled_file), str(compiled_file)
+            ], check=True)
+            forced_file = Path(temp_dir) / f"forced{self.args.compiled_ext}"
+            self.run_command([
+                self.bin_path, "compile", "--force",
+                str(forced_file), str(compiled_file)
+            ], check=True)
+            if not forced_file.exists():
+                raise AssertionError("Forced compile did not write its output")
+
+if __name__ == "__main__":
+    test = SwappedArgsTest()
+    sys.exit(test.main())